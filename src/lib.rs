@@ -5,36 +5,156 @@
 //!
 //! This is just a core library, to actually show anything on the screen, you have to write
 //! code that reads the information contained in this library and presents it.
+//!
+//! Enable the `json` feature to export [`IMMEDIATE`] or [`PERSISTENT`] as JSON, so an
+//! out-of-process tool (a dev console, a log collector) can consume them without linking
+//! against this crate.
+//!
+//! Beyond the two default buffers, [`channel`]/[`imm_channel`] and the `per_ch!`/`imm_ch!` macros
+//! let a game gate subsystems (`"render"`, `"net"`, `"ai"`) independently, instead of one global
+//! on/off.
 #![warn(missing_docs)]
 
-use std::sync::{
-    atomic::{AtomicBool, AtomicU32, Ordering},
-    Mutex,
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Mutex, OnceLock,
+    },
 };
 
 /// Immediate messages that are for this frame only
-pub static IMMEDIATE: MsgBuf<String> = MsgBuf::new(false);
+pub static IMMEDIATE: MsgBuf<ImmEntry> = MsgBuf::new(false);
 /// Persistent messages that last between frames
 pub static PERSISTENT: MsgBuf<PerEntry> = MsgBuf::new(false);
 
+/// Severity level of a debug message
+///
+/// Ordered from least to most severe, so `level >= min_level` comparisons work as expected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[repr(u32)]
+pub enum Level {
+    /// Fine-grained information, useful for tracing execution in detail
+    Trace = 0,
+    /// Information useful while debugging
+    Debug = 1,
+    /// General informational messages
+    Info = 2,
+    /// Something unexpected happened, but it's not fatal
+    Warn = 3,
+    /// A serious problem occurred
+    Error = 4,
+}
+
+impl Level {
+    fn from_u32(v: u32) -> Self {
+        match v {
+            0 => Level::Trace,
+            1 => Level::Debug,
+            2 => Level::Info,
+            3 => Level::Warn,
+            _ => Level::Error,
+        }
+    }
+}
+
+/// A message that carries a [`Level`], so a [`MsgBuf`] can filter it by severity
+pub trait Leveled {
+    /// Returns the severity level of this message
+    fn level(&self) -> Level;
+}
+
+thread_local! {
+    /// This thread's staging slot for every `MsgBuf` it has ever pushed to, keyed by the
+    /// buffer's address plus its message type. A `thread_local!` can't itself be generic over
+    /// `Msg`, so the slots are type-erased here and downcast back in `MsgBuf::stage`.
+    static STAGES: RefCell<HashMap<(usize, TypeId), Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
 /// A statically globally accessible message buffer
-pub struct MsgBuf<Msg> {
+pub struct MsgBuf<Msg: 'static> {
     msgs: Mutex<Vec<Msg>>,
     enabled: AtomicBool,
+    min_level: AtomicU32,
+    /// Every thread's staging buffer that has ever pushed to this `MsgBuf`, so [`flush`](Self::flush)
+    /// can find and drain them. Staging buffers are intentionally leaked (`'static`): there's one
+    /// per logging thread for the process's lifetime, which is negligible for a debug buffer.
+    stages: Mutex<Vec<&'static Mutex<Vec<Msg>>>>,
 }
 
-impl<Msg> MsgBuf<Msg> {
+impl<Msg: Leveled + 'static> MsgBuf<Msg> {
     /// Create a new empty message buffer
     pub const fn new(enabled: bool) -> Self {
         Self {
             msgs: Mutex::new(Vec::new()),
             enabled: AtomicBool::new(enabled),
+            min_level: AtomicU32::new(Level::Trace as u32),
+            stages: Mutex::new(Vec::new()),
         }
     }
     /// Push a message to the buffer
+    ///
+    /// Does nothing if the buffer isn't enabled, or if the message's [`Level`] is below the
+    /// buffer's [`min_level`](Self::min_level). Otherwise, the message lands in this thread's
+    /// staging buffer without touching the shared lock; call [`flush`](Self::flush) (or
+    /// [`inc_frame`]) to merge staged messages from every thread into the buffer proper.
     pub fn push(&self, msg: Msg) {
-        if self.enabled.load(Ordering::Acquire) {
-            self.msgs.lock().unwrap().push(msg);
+        if self.enabled.load(Ordering::Acquire) && msg.level() >= self.min_level() {
+            self.stage(msg);
+        }
+    }
+    /// Push a lazily-built message to the buffer
+    ///
+    /// Unlike [`push`](Self::push), the message itself is only constructed if the buffer is
+    /// enabled and `level` clears the buffer's [`min_level`](Self::min_level). This lets callers
+    /// (notably the `imm!`/`per!` macros) skip the cost of `format!`-ing their arguments entirely
+    /// when nobody is going to read the result.
+    pub fn push_with(&self, level: Level, f: impl FnOnce() -> Msg) {
+        if self.enabled.load(Ordering::Acquire) && level >= self.min_level() {
+            self.stage(f());
+        }
+    }
+    fn stage(&self, msg: Msg) {
+        let key = (self as *const Self as usize, TypeId::of::<Msg>());
+        STAGES.with(|stages| {
+            let mut stages = stages.borrow_mut();
+            let boxed = stages.entry(key).or_insert_with(|| {
+                let stage: &'static Mutex<Vec<Msg>> = Box::leak(Box::new(Mutex::new(Vec::new())));
+                self.stages.lock().unwrap().push(stage);
+                Box::new(stage)
+            });
+            let stage: &&'static Mutex<Vec<Msg>> = boxed
+                .downcast_ref()
+                .expect("staging slot type mismatch for this MsgBuf");
+            stage.lock().unwrap().push(msg);
+        });
+    }
+    /// Merges every thread's staged messages into the buffer
+    ///
+    /// Ordering within a single thread's messages is preserved; across threads, messages are
+    /// ordered by flush order rather than push order, since that's all a lock-free staging
+    /// buffer can promise. [`inc_frame`] calls this for [`IMMEDIATE`] and the immediate named
+    /// channels, and [`flush_with`](Self::flush_with) for [`PERSISTENT`] and the persistent ones.
+    pub fn flush(&self) {
+        self.flush_with(|_| {});
+    }
+    /// Like [`flush`](Self::flush), but calls `tag` on every message as it's merged in
+    ///
+    /// This is how [`PerEntry::frame`] gets stamped with the frame a message was flushed in,
+    /// rather than the frame (possibly stale, if a worker thread is lagging behind) it was
+    /// staged in.
+    pub fn flush_with(&self, mut tag: impl FnMut(&mut Msg)) {
+        let stages = self.stages.lock().unwrap();
+        let mut msgs = self.msgs.lock().unwrap();
+        for stage in stages.iter() {
+            let mut staged = stage.lock().unwrap();
+            for msg in staged.iter_mut() {
+                tag(msg);
+            }
+            msgs.append(&mut staged);
         }
     }
     /// Toggle whether the buffer is "enabled". If it's not enabled, pushing won't do anything.
@@ -54,6 +174,14 @@ impl<Msg> MsgBuf<Msg> {
     pub fn set_enabled(&self, enabled: bool) {
         self.enabled.store(enabled, Ordering::Release)
     }
+    /// Sets the minimum [`Level`] a message needs to have to be pushed into the buffer
+    pub fn set_min_level(&self, level: Level) {
+        self.min_level.store(level as u32, Ordering::Release);
+    }
+    /// Returns the minimum [`Level`] a message needs to have to be pushed into the buffer
+    pub fn min_level(&self) -> Level {
+        Level::from_u32(self.min_level.load(Ordering::Acquire))
+    }
     /// Removes old messages from the buffer, until it's `max` length.
     pub fn trim_old(&self, max: usize) {
         let mut msgs = self.msgs.lock().unwrap();
@@ -77,9 +205,55 @@ impl<Msg> MsgBuf<Msg> {
     }
 }
 
+#[cfg(feature = "json")]
+impl<Msg: Leveled + serde::Serialize> MsgBuf<Msg> {
+    /// Snapshot the buffer as a JSON array, under the lock
+    ///
+    /// This gives external tooling (a dev console, a log collector) a stable wire format,
+    /// decoupled from the in-process rendering code, which remains the consumer's job.
+    pub fn to_json(&self) -> String {
+        let msgs = self.msgs.lock().unwrap();
+        serde_json::to_string(&*msgs).expect("buffered messages should always serialize")
+    }
+    /// Write the buffer out as newline-delimited JSON, one object per message
+    ///
+    /// If `clear` is `true`, the buffer is cleared as it's written.
+    pub fn drain_json_lines(
+        &self,
+        w: &mut impl std::io::Write,
+        clear: bool,
+    ) -> std::io::Result<()> {
+        let mut msgs = self.msgs.lock().unwrap();
+        for msg in msgs.iter() {
+            serde_json::to_writer(&mut *w, msg).expect("buffered message should always serialize");
+            writeln!(w)?;
+        }
+        if clear {
+            msgs.clear();
+        }
+        Ok(())
+    }
+}
+
 static FRAME_COUNTER: AtomicU32 = AtomicU32::new(0);
 
+/// Immediate message entry with a severity level
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ImmEntry {
+    /// The severity of this message
+    pub level: Level,
+    /// The message text
+    pub text: String,
+}
+
+impl Leveled for ImmEntry {
+    fn level(&self) -> Level {
+        self.level
+    }
+}
+
 /// Persistent info entry with a frame stamp
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 pub struct PerEntry {
     /// The frame this information was recorded on
     pub frame: u32,
@@ -87,9 +261,18 @@ pub struct PerEntry {
     pub info: String,
     /// Source code location of the entry, if any
     pub src_loc: Option<SrcLoc>,
+    /// The severity of this message
+    pub level: Level,
+}
+
+impl Leveled for PerEntry {
+    fn level(&self) -> Level {
+        self.level
+    }
 }
 
 /// Source code location of an entry
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 #[allow(missing_docs)]
 pub struct SrcLoc {
     pub file: &'static str,
@@ -109,19 +292,93 @@ macro_rules! _gamedebug_core_src_loc {
     };
 }
 
-/// Add persistent information
-pub fn per(info: String, src_loc: Option<SrcLoc>) {
-    PERSISTENT.push(PerEntry {
+/// Builds a [`PerEntry`] for the `per!`/`per_warn!`/`per_error!`/`per_dbg!`/`per_ch!` macros
+///
+/// Not part of the public API; shared so those macros don't each repeat the same struct literal.
+#[doc(hidden)]
+pub fn __per_entry(level: Level, info: String, src_loc: Option<SrcLoc>) -> PerEntry {
+    PerEntry {
         frame: frame(),
         info,
         src_loc,
-    });
+        level,
+    }
+}
+
+fn per_channels() -> &'static Mutex<HashMap<&'static str, &'static MsgBuf<PerEntry>>> {
+    static CHANNELS: OnceLock<Mutex<HashMap<&'static str, &'static MsgBuf<PerEntry>>>> =
+        OnceLock::new();
+    CHANNELS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn imm_channels() -> &'static Mutex<HashMap<&'static str, &'static MsgBuf<ImmEntry>>> {
+    static CHANNELS: OnceLock<Mutex<HashMap<&'static str, &'static MsgBuf<ImmEntry>>>> =
+        OnceLock::new();
+    CHANNELS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Returns the named persistent debug channel, creating it (disabled) the first time it's named
+///
+/// Unlike [`IMMEDIATE`] and [`PERSISTENT`], channels aren't hardcoded: a game can gate as many
+/// subsystems as it wants, each toggled independently, by just picking a name for it. This is
+/// the `per_ch!` side; see [`imm_channel`] for the `imm_ch!` side.
+pub fn channel(name: &'static str) -> &'static MsgBuf<PerEntry> {
+    let mut channels = per_channels().lock().unwrap();
+    channels
+        .entry(name)
+        .or_insert_with(|| Box::leak(Box::new(MsgBuf::new(false))))
+}
+
+/// Returns the named immediate debug channel, creating it (disabled) the first time it's named
+///
+/// This is the `imm_ch!` side of named channels; see [`channel`] for the `per_ch!` side.
+pub fn imm_channel(name: &'static str) -> &'static MsgBuf<ImmEntry> {
+    let mut channels = imm_channels().lock().unwrap();
+    channels
+        .entry(name)
+        .or_insert_with(|| Box::leak(Box::new(MsgBuf::new(false))))
+}
+
+/// Enables the named persistent debug channel, creating it first if it doesn't exist yet
+pub fn enable_channel(name: &'static str) {
+    channel(name).set_enabled(true);
+}
+
+/// Enables the named immediate debug channel, creating it first if it doesn't exist yet
+pub fn enable_imm_channel(name: &'static str) {
+    imm_channel(name).set_enabled(true);
+}
+
+/// Returns the name of every persistent channel that has been named so far, e.g. via
+/// [`channel`], [`enable_channel`], or the `per_ch!` macro
+///
+/// Lets a dev console list the known categories and flip them at runtime.
+pub fn channel_names() -> Vec<&'static str> {
+    per_channels().lock().unwrap().keys().copied().collect()
+}
+
+/// Returns the name of every immediate channel that has been named so far, e.g. via
+/// [`imm_channel`], [`enable_imm_channel`], or the `imm_ch!` macro
+pub fn imm_channel_names() -> Vec<&'static str> {
+    imm_channels().lock().unwrap().keys().copied().collect()
 }
 
 /// Increment the frame counter. Do this every frame.
+///
+/// Also flushes [`IMMEDIATE`], [`PERSISTENT`], and every named channel, merging in any messages
+/// staged by other threads since the last call and stamping persistent entries with the frame
+/// that's ending.
 pub fn inc_frame() {
-    let frame = FRAME_COUNTER.load(Ordering::Acquire);
-    FRAME_COUNTER.store(frame + 1, Ordering::Release);
+    let ending_frame = FRAME_COUNTER.load(Ordering::Acquire);
+    IMMEDIATE.flush();
+    PERSISTENT.flush_with(|entry| entry.frame = ending_frame);
+    for chan in imm_channels().lock().unwrap().values() {
+        chan.flush();
+    }
+    for chan in per_channels().lock().unwrap().values() {
+        chan.flush_with(|entry| entry.frame = ending_frame);
+    }
+    FRAME_COUNTER.store(ending_frame + 1, Ordering::Release);
 }
 
 /// Query the frame counter
@@ -129,11 +386,45 @@ pub fn frame() -> u32 {
     FRAME_COUNTER.load(Ordering::Acquire)
 }
 
-/// `println!`-like macro for pushing an immediate message
+/// `println!`-like macro for pushing an immediate message at [`Level::Info`]
+///
+/// The arguments are only formatted if [`IMMEDIATE`] is enabled and its minimum level is
+/// [`Level::Info`] or lower, so this is cheap to leave in hot loops when debugging is off.
 #[macro_export]
 macro_rules! imm {
     ($($arg:tt)*) => {{
-        $crate::IMMEDIATE.push(format!($($arg)*));
+        if $crate::IMMEDIATE.enabled() {
+            $crate::IMMEDIATE.push_with($crate::Level::Info, || $crate::ImmEntry {
+                level: $crate::Level::Info,
+                text: format!($($arg)*),
+            });
+        }
+    }};
+}
+
+/// `println!`-like macro for pushing an immediate message at [`Level::Warn`]
+#[macro_export]
+macro_rules! imm_warn {
+    ($($arg:tt)*) => {{
+        if $crate::IMMEDIATE.enabled() {
+            $crate::IMMEDIATE.push_with($crate::Level::Warn, || $crate::ImmEntry {
+                level: $crate::Level::Warn,
+                text: format!($($arg)*),
+            });
+        }
+    }};
+}
+
+/// `println!`-like macro for pushing an immediate message at [`Level::Error`]
+#[macro_export]
+macro_rules! imm_error {
+    ($($arg:tt)*) => {{
+        if $crate::IMMEDIATE.enabled() {
+            $crate::IMMEDIATE.push_with($crate::Level::Error, || $crate::ImmEntry {
+                level: $crate::Level::Error,
+                text: format!($($arg)*),
+            });
+        }
     }};
 }
 
@@ -142,10 +433,13 @@ macro_rules! imm {
 macro_rules! imm_dbg {
     ($val:expr $(,)?) => {{
         if $crate::IMMEDIATE.enabled() {
-            $crate::IMMEDIATE.push(format!(
-                concat!(file!(), ":", line!(), ": ", stringify!($val), ": {:#?}"),
-                $val
-            ));
+            $crate::IMMEDIATE.push_with($crate::Level::Debug, || $crate::ImmEntry {
+                level: $crate::Level::Debug,
+                text: format!(
+                    concat!(file!(), ":", line!(), ": ", stringify!($val), ": {:#?}"),
+                    $val
+                ),
+            });
         }
         $val
     }};
@@ -154,11 +448,42 @@ macro_rules! imm_dbg {
     }}
 }
 
-/// `println!`-like macro for pushing a persistent message
+/// `println!`-like macro for pushing a persistent message at [`Level::Info`]
+///
+/// The arguments are only formatted if [`PERSISTENT`] is enabled and its minimum level is
+/// [`Level::Info`] or lower, so this is cheap to leave in hot loops when debugging is off.
 #[macro_export]
 macro_rules! per {
     ($($arg:tt)*) => {{
-        $crate::per(format!($($arg)*), Some($crate::_gamedebug_core_src_loc!()));
+        if $crate::PERSISTENT.enabled() {
+            $crate::PERSISTENT.push_with($crate::Level::Info, || {
+                $crate::__per_entry($crate::Level::Info, format!($($arg)*), Some($crate::_gamedebug_core_src_loc!()))
+            });
+        }
+    }};
+}
+
+/// `println!`-like macro for pushing a persistent message at [`Level::Warn`]
+#[macro_export]
+macro_rules! per_warn {
+    ($($arg:tt)*) => {{
+        if $crate::PERSISTENT.enabled() {
+            $crate::PERSISTENT.push_with($crate::Level::Warn, || {
+                $crate::__per_entry($crate::Level::Warn, format!($($arg)*), Some($crate::_gamedebug_core_src_loc!()))
+            });
+        }
+    }};
+}
+
+/// `println!`-like macro for pushing a persistent message at [`Level::Error`]
+#[macro_export]
+macro_rules! per_error {
+    ($($arg:tt)*) => {{
+        if $crate::PERSISTENT.enabled() {
+            $crate::PERSISTENT.push_with($crate::Level::Error, || {
+                $crate::__per_entry($crate::Level::Error, format!($($arg)*), Some($crate::_gamedebug_core_src_loc!()))
+            });
+        }
     }};
 }
 
@@ -166,16 +491,175 @@ macro_rules! per {
 #[macro_export]
 macro_rules! per_dbg {
     ($x:expr) => {{
-        $crate::per(
-            format!(concat!(stringify!($x), ": {:#?}"), $x),
-            Some($crate::_gamedebug_core_src_loc!()),
-        );
+        if $crate::PERSISTENT.enabled() {
+            $crate::PERSISTENT.push_with($crate::Level::Debug, || {
+                $crate::__per_entry(
+                    $crate::Level::Debug,
+                    format!(concat!(stringify!($x), ": {:#?}"), $x),
+                    Some($crate::_gamedebug_core_src_loc!()),
+                )
+            });
+        }
         $x
     }};
 }
 
+/// `println!`-like macro for pushing an immediate message to a named channel, at [`Level::Info`]
+///
+/// The channel is created (disabled) the first time it's named, same as [`imm_channel`].
+#[macro_export]
+macro_rules! imm_ch {
+    ($channel:expr, $($arg:tt)*) => {{
+        let chan = $crate::imm_channel($channel);
+        if chan.enabled() {
+            chan.push_with($crate::Level::Info, || $crate::ImmEntry {
+                level: $crate::Level::Info,
+                text: format!($($arg)*),
+            });
+        }
+    }};
+}
+
+/// `println!`-like macro for pushing a persistent message to a named channel, at [`Level::Info`]
+///
+/// The channel is created (disabled) the first time it's named, same as [`channel`].
+#[macro_export]
+macro_rules! per_ch {
+    ($channel:expr, $($arg:tt)*) => {{
+        let chan = $crate::channel($channel);
+        if chan.enabled() {
+            chan.push_with($crate::Level::Info, || {
+                $crate::__per_entry($crate::Level::Info, format!($($arg)*), Some($crate::_gamedebug_core_src_loc!()))
+            });
+        }
+    }};
+}
+
 #[test]
 fn basic_macro_sanity_test() {
     per!("Hi!");
     per_dbg!(42);
 }
+
+#[test]
+fn min_level_filters_messages_below_threshold() {
+    let buf: MsgBuf<PerEntry> = MsgBuf::new(true);
+    buf.set_min_level(Level::Warn);
+    buf.push(PerEntry {
+        frame: 0,
+        info: "ignored".to_string(),
+        src_loc: None,
+        level: Level::Info,
+    });
+    buf.push(PerEntry {
+        frame: 0,
+        info: "kept".to_string(),
+        src_loc: None,
+        level: Level::Error,
+    });
+    buf.flush();
+    assert_eq!(buf.len(), 1);
+    buf.for_each(|entry| assert_eq!(entry.info, "kept"));
+}
+
+#[test]
+fn flush_with_merges_staged_messages_and_applies_tag() {
+    let buf: MsgBuf<PerEntry> = MsgBuf::new(true);
+    buf.push(PerEntry {
+        frame: 0,
+        info: "a".to_string(),
+        src_loc: None,
+        level: Level::Info,
+    });
+    buf.push(PerEntry {
+        frame: 0,
+        info: "b".to_string(),
+        src_loc: None,
+        level: Level::Info,
+    });
+    assert_eq!(buf.len(), 0, "pushed messages are staged, not yet merged");
+    buf.flush_with(|entry| entry.frame = 7);
+    assert_eq!(buf.len(), 2);
+    let mut seen = Vec::new();
+    buf.for_each(|entry| {
+        assert_eq!(entry.frame, 7);
+        seen.push(entry.info.clone());
+    });
+    assert_eq!(seen, vec!["a".to_string(), "b".to_string()]);
+}
+
+#[test]
+fn named_channel_is_disabled_until_enabled_and_round_trips_messages() {
+    let chan = channel("test::round_trip_channel");
+    assert!(!chan.enabled(), "channels start disabled until enabled");
+    chan.push(PerEntry {
+        frame: 0,
+        info: "dropped".to_string(),
+        src_loc: None,
+        level: Level::Info,
+    });
+    chan.flush();
+    assert_eq!(chan.len(), 0, "a disabled channel shouldn't accept pushes");
+
+    enable_channel("test::round_trip_channel");
+    assert!(channel_names().contains(&"test::round_trip_channel"));
+    chan.push(PerEntry {
+        frame: 0,
+        info: "kept".to_string(),
+        src_loc: None,
+        level: Level::Info,
+    });
+    chan.flush();
+    assert_eq!(chan.len(), 1);
+    chan.for_each(|entry| assert_eq!(entry.info, "kept"));
+}
+
+#[cfg(all(test, feature = "json"))]
+#[test]
+fn to_json_serializes_buffered_entries() {
+    let buf: MsgBuf<PerEntry> = MsgBuf::new(true);
+    buf.push(PerEntry {
+        frame: 3,
+        info: "hello".to_string(),
+        src_loc: None,
+        level: Level::Info,
+    });
+    buf.flush();
+    let json = buf.to_json();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let entries = parsed.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["frame"], 3);
+    assert_eq!(entries[0]["info"], "hello");
+}
+
+#[cfg(all(test, feature = "json"))]
+#[test]
+fn drain_json_lines_writes_one_line_per_message_and_clears_when_asked() {
+    let buf: MsgBuf<PerEntry> = MsgBuf::new(true);
+    buf.push(PerEntry {
+        frame: 1,
+        info: "a".to_string(),
+        src_loc: None,
+        level: Level::Info,
+    });
+    buf.push(PerEntry {
+        frame: 2,
+        info: "b".to_string(),
+        src_loc: None,
+        level: Level::Info,
+    });
+    buf.flush();
+    let mut out = Vec::new();
+    buf.drain_json_lines(&mut out, true).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2);
+    for line in &lines {
+        serde_json::from_str::<serde_json::Value>(line).unwrap();
+    }
+    assert!(
+        buf.is_empty(),
+        "drain_json_lines(clear = true) should clear the buffer"
+    );
+}
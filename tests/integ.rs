@@ -1,9 +1,9 @@
-use gamedebug_core::{imm, imm_dbg, per, per_dbg};
+use gamedebug_core::{imm, imm_dbg, per, per_dbg, IMMEDIATE};
 
 #[test]
 fn test() {
-    gamedebug_core::toggle();
-    dbg!(gamedebug_core::enabled());
+    IMMEDIATE.toggle();
+    dbg!(IMMEDIATE.enabled());
     per!("Hi!");
     imm!("Hi!");
     per_dbg!(42);